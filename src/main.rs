@@ -1,9 +1,335 @@
+use chrono::{NaiveDate, Utc};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, env, vec};
+use std::{collections::HashMap, env, fmt, fs, path::PathBuf, str::FromStr, vec};
 use surf;
 use tokio;
 
+/// The ISO 4217 currencies supported by the Frankfurter API, plus the handful
+/// of crypto assets routed to [`AlphavantageProvider`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Currency {
+    Aud,
+    Bgn,
+    Brl,
+    Cad,
+    Chf,
+    Cny,
+    Czk,
+    Dkk,
+    Eur,
+    Gbp,
+    Hkd,
+    Huf,
+    Idr,
+    Ils,
+    Inr,
+    Isk,
+    Jpy,
+    Krw,
+    Mxn,
+    Myr,
+    Nok,
+    Nzd,
+    Php,
+    Pln,
+    Ron,
+    Sek,
+    Sgd,
+    Thb,
+    Try,
+    Usd,
+    Zar,
+    Btc,
+    Eth,
+    Usdt,
+    Xrp,
+    Ltc,
+    Doge,
+}
+
+impl Currency {
+    /// Every currency this tool knows how to convert, in no particular order.
+    pub const ALL: &'static [Currency] = &[
+        Currency::Aud,
+        Currency::Bgn,
+        Currency::Brl,
+        Currency::Cad,
+        Currency::Chf,
+        Currency::Cny,
+        Currency::Czk,
+        Currency::Dkk,
+        Currency::Eur,
+        Currency::Gbp,
+        Currency::Hkd,
+        Currency::Huf,
+        Currency::Idr,
+        Currency::Ils,
+        Currency::Inr,
+        Currency::Isk,
+        Currency::Jpy,
+        Currency::Krw,
+        Currency::Mxn,
+        Currency::Myr,
+        Currency::Nok,
+        Currency::Nzd,
+        Currency::Php,
+        Currency::Pln,
+        Currency::Ron,
+        Currency::Sek,
+        Currency::Sgd,
+        Currency::Thb,
+        Currency::Try,
+        Currency::Usd,
+        Currency::Zar,
+        Currency::Btc,
+        Currency::Eth,
+        Currency::Usdt,
+        Currency::Xrp,
+        Currency::Ltc,
+        Currency::Doge,
+    ];
+
+    /// Whether this is a crypto asset, routed to [`AlphavantageProvider`] instead of Frankfurter.
+    pub fn is_crypto(&self) -> bool {
+        matches!(
+            self,
+            Currency::Btc | Currency::Eth | Currency::Usdt | Currency::Xrp | Currency::Ltc | Currency::Doge
+        )
+    }
+
+    /// The uppercase currency code, e.g. `"USD"`: the ISO 4217 code for fiat
+    /// currencies, the common ticker (e.g. `"BTC"`) for crypto assets.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Aud => "AUD",
+            Currency::Bgn => "BGN",
+            Currency::Brl => "BRL",
+            Currency::Cad => "CAD",
+            Currency::Chf => "CHF",
+            Currency::Cny => "CNY",
+            Currency::Czk => "CZK",
+            Currency::Dkk => "DKK",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Hkd => "HKD",
+            Currency::Huf => "HUF",
+            Currency::Idr => "IDR",
+            Currency::Ils => "ILS",
+            Currency::Inr => "INR",
+            Currency::Isk => "ISK",
+            Currency::Jpy => "JPY",
+            Currency::Krw => "KRW",
+            Currency::Mxn => "MXN",
+            Currency::Myr => "MYR",
+            Currency::Nok => "NOK",
+            Currency::Nzd => "NZD",
+            Currency::Php => "PHP",
+            Currency::Pln => "PLN",
+            Currency::Ron => "RON",
+            Currency::Sek => "SEK",
+            Currency::Sgd => "SGD",
+            Currency::Thb => "THB",
+            Currency::Try => "TRY",
+            Currency::Usd => "USD",
+            Currency::Zar => "ZAR",
+            Currency::Btc => "BTC",
+            Currency::Eth => "ETH",
+            Currency::Usdt => "USDT",
+            Currency::Xrp => "XRP",
+            Currency::Ltc => "LTC",
+            Currency::Doge => "DOGE",
+        }
+    }
+
+    /// The currency's symbol, e.g. `"$"` for USD.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Currency::Aud => "A$",
+            Currency::Bgn => "лв",
+            Currency::Brl => "R$",
+            Currency::Cad => "C$",
+            Currency::Chf => "CHF",
+            Currency::Cny => "¥",
+            Currency::Czk => "Kč",
+            Currency::Dkk => "kr",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+            Currency::Hkd => "HK$",
+            Currency::Huf => "Ft",
+            Currency::Idr => "Rp",
+            Currency::Ils => "₪",
+            Currency::Inr => "₹",
+            Currency::Isk => "kr",
+            Currency::Jpy => "¥",
+            Currency::Krw => "₩",
+            Currency::Mxn => "MX$",
+            Currency::Myr => "RM",
+            Currency::Nok => "kr",
+            Currency::Nzd => "NZ$",
+            Currency::Php => "₱",
+            Currency::Pln => "zł",
+            Currency::Ron => "lei",
+            Currency::Sek => "kr",
+            Currency::Sgd => "S$",
+            Currency::Thb => "฿",
+            Currency::Try => "₺",
+            Currency::Usd => "$",
+            Currency::Zar => "R",
+            Currency::Btc => "₿",
+            Currency::Eth => "Ξ",
+            Currency::Usdt => "₮",
+            Currency::Xrp => "XRP",
+            Currency::Ltc => "Ł",
+            Currency::Doge => "Ð",
+        }
+    }
+
+    /// The full English name, e.g. `"US Dollar"`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Currency::Aud => "Australian Dollar",
+            Currency::Bgn => "Bulgarian Lev",
+            Currency::Brl => "Brazilian Real",
+            Currency::Cad => "Canadian Dollar",
+            Currency::Chf => "Swiss Franc",
+            Currency::Cny => "Chinese Yuan",
+            Currency::Czk => "Czech Koruna",
+            Currency::Dkk => "Danish Krone",
+            Currency::Eur => "Euro",
+            Currency::Gbp => "British Pound",
+            Currency::Hkd => "Hong Kong Dollar",
+            Currency::Huf => "Hungarian Forint",
+            Currency::Idr => "Indonesian Rupiah",
+            Currency::Ils => "Israeli New Shekel",
+            Currency::Inr => "Indian Rupee",
+            Currency::Isk => "Icelandic Krona",
+            Currency::Jpy => "Japanese Yen",
+            Currency::Krw => "South Korean Won",
+            Currency::Mxn => "Mexican Peso",
+            Currency::Myr => "Malaysian Ringgit",
+            Currency::Nok => "Norwegian Krone",
+            Currency::Nzd => "New Zealand Dollar",
+            Currency::Php => "Philippine Peso",
+            Currency::Pln => "Polish Zloty",
+            Currency::Ron => "Romanian Leu",
+            Currency::Sek => "Swedish Krona",
+            Currency::Sgd => "Singapore Dollar",
+            Currency::Thb => "Thai Baht",
+            Currency::Try => "Turkish Lira",
+            Currency::Usd => "US Dollar",
+            Currency::Zar => "South African Rand",
+            Currency::Btc => "Bitcoin",
+            Currency::Eth => "Ethereum",
+            Currency::Usdt => "Tether",
+            Currency::Xrp => "XRP",
+            Currency::Ltc => "Litecoin",
+            Currency::Doge => "Dogecoin",
+        }
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+/// Why a piece of user input failed to parse as a [`Currency`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CurrencyError {
+    InvalidLength(usize),
+    InvalidCharacter(char),
+    Unsupported(String),
+}
+
+impl fmt::Display for CurrencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurrencyError::InvalidLength(len) => {
+                write!(f, "currency code must be 3 or 4 letters, got {}", len)
+            }
+            CurrencyError::InvalidCharacter(c) => {
+                write!(f, "currency code contains invalid character '{}'", c)
+            }
+            CurrencyError::Unsupported(code) => {
+                write!(f, "'{}' is not a supported currency", code)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CurrencyError {}
+
+impl FromStr for Currency {
+    type Err = CurrencyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 3 && s.len() != 4 {
+            return Err(CurrencyError::InvalidLength(s.len()));
+        }
+        if let Some(c) = s.chars().find(|c| !c.is_ascii_alphabetic()) {
+            return Err(CurrencyError::InvalidCharacter(c));
+        }
+
+        match s.to_uppercase().as_str() {
+            "AUD" => Ok(Currency::Aud),
+            "BGN" => Ok(Currency::Bgn),
+            "BRL" => Ok(Currency::Brl),
+            "CAD" => Ok(Currency::Cad),
+            "CHF" => Ok(Currency::Chf),
+            "CNY" => Ok(Currency::Cny),
+            "CZK" => Ok(Currency::Czk),
+            "DKK" => Ok(Currency::Dkk),
+            "EUR" => Ok(Currency::Eur),
+            "GBP" => Ok(Currency::Gbp),
+            "HKD" => Ok(Currency::Hkd),
+            "HUF" => Ok(Currency::Huf),
+            "IDR" => Ok(Currency::Idr),
+            "ILS" => Ok(Currency::Ils),
+            "INR" => Ok(Currency::Inr),
+            "ISK" => Ok(Currency::Isk),
+            "JPY" => Ok(Currency::Jpy),
+            "KRW" => Ok(Currency::Krw),
+            "MXN" => Ok(Currency::Mxn),
+            "MYR" => Ok(Currency::Myr),
+            "NOK" => Ok(Currency::Nok),
+            "NZD" => Ok(Currency::Nzd),
+            "PHP" => Ok(Currency::Php),
+            "PLN" => Ok(Currency::Pln),
+            "RON" => Ok(Currency::Ron),
+            "SEK" => Ok(Currency::Sek),
+            "SGD" => Ok(Currency::Sgd),
+            "THB" => Ok(Currency::Thb),
+            "TRY" => Ok(Currency::Try),
+            "USD" => Ok(Currency::Usd),
+            "ZAR" => Ok(Currency::Zar),
+            "BTC" => Ok(Currency::Btc),
+            "ETH" => Ok(Currency::Eth),
+            "USDT" => Ok(Currency::Usdt),
+            "XRP" => Ok(Currency::Xrp),
+            "LTC" => Ok(Currency::Ltc),
+            "DOGE" => Ok(Currency::Doge),
+            other => Err(CurrencyError::Unsupported(other.to_string())),
+        }
+    }
+}
+
+/// Renders the supported currency codes as a word-wrapped block for error messages.
+fn supported_currencies_block() -> String {
+    Currency::ALL
+        .chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .map(|c| c.code())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct FrankfurterResponse {
     base: String,
@@ -11,6 +337,52 @@ struct FrankfurterResponse {
     rates: HashMap<String, f64>,
 }
 
+/// Where cached rate tables live, following the XDG Base Directory spec.
+fn cache_dir() -> PathBuf {
+    let base = env::var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".to_string())).join(".cache"));
+    base.join("sherlock-currency")
+}
+
+fn cache_path(base: &Currency) -> PathBuf {
+    cache_dir().join(format!("{}.json", base.code()))
+}
+
+/// Best-effort write of the latest full rate table for `base`; cache misses
+/// on write are not fatal to the conversion that triggered them.
+fn save_rate_cache(base: &Currency, response: &FrankfurterResponse) {
+    if fs::create_dir_all(cache_dir()).is_err() {
+        return;
+    }
+    if let Ok(json) = serde_json::to_string(response) {
+        let _ = fs::write(cache_path(base), json);
+    }
+}
+
+fn load_rate_cache(base: &Currency) -> Option<FrankfurterResponse> {
+    let contents = fs::read_to_string(cache_path(base)).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Formats a converted amount with enough precision to stay meaningful for
+/// low-unit-value currencies. A flat `{:.2}` rounds ordinary crypto amounts
+/// (e.g. 0.00045879 BTC) down to "0.00", silently hiding the result, so
+/// sub-1 amounts get more decimal places with trailing zeros trimmed.
+fn format_amount(value: f64) -> String {
+    if value.abs() < 1.0 {
+        let formatted = format!("{:.8}", value);
+        let trimmed = formatted.trim_end_matches('0').trim_end_matches('.');
+        if trimmed.is_empty() || trimmed == "-" {
+            "0".to_string()
+        } else {
+            trimmed.to_string()
+        }
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct SherlockPipeResponse {
     title: String,
@@ -29,17 +401,19 @@ pub struct ApplicationAction {
 }
 
 impl ApplicationAction {
-    fn from_conversion(amount: f64, from: &str, to: &str, result: f64, rate: f64) -> Self {
-        let result_text = format!("{:.2} {}", result, to.to_uppercase());
+    fn from_conversion(amount: f64, from: &Currency, to: &Currency, result: f64, rate: f64) -> Self {
+        let result_text = format!("{}{} {}", to.symbol(), format_amount(result), to);
         let detailed_info = format!(
-            "{:.2} {} = {:.2} {}\nExchange Rate: 1 {} = {:.6} {}",
-            amount,
-            from.to_uppercase(),
-            result,
-            to.to_uppercase(),
-            from.to_uppercase(),
+            "{} {} ({}) = {} {} ({})\nExchange Rate: 1 {} = {:.6} {}",
+            format_amount(amount),
+            from,
+            from.name(),
+            format_amount(result),
+            to,
+            to.name(),
+            from,
             rate,
-            to.to_uppercase()
+            to
         );
 
         Self {
@@ -52,82 +426,498 @@ impl ApplicationAction {
     }
 }
 
-fn parse_currency_input(input: &str) -> Result<(f64, String, String), String> {
+/// Why the raw input couldn't be turned into a conversion request.
+#[derive(Debug)]
+enum ParseError {
+    /// The input didn't match any recognized conversion pattern.
+    Format(String),
+    /// The input matched a pattern, but named a currency we don't support.
+    Currency(CurrencyError),
+    /// The input matched a pattern, but its `on YYYY-MM-DD` clause was
+    /// malformed or named a date in the future.
+    Date(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Format(msg) => write!(f, "{}", msg),
+            ParseError::Currency(err) => write!(f, "{}", err),
+            ParseError::Date(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+/// A token in an arithmetic amount expression.
+#[derive(Debug, Clone, PartialEq)]
+enum ExprToken {
+    Number(f64),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+}
+
+impl ExprToken {
+    fn precedence(&self) -> u8 {
+        match self {
+            ExprToken::Plus | ExprToken::Minus => 1,
+            ExprToken::Star | ExprToken::Slash => 2,
+            _ => 0,
+        }
+    }
+}
+
+fn tokenize_expression(expr: &str) -> Result<Vec<ExprToken>, String> {
+    let chars: Vec<char> = expr.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '+' => {
+                tokens.push(ExprToken::Plus);
+                i += 1;
+            }
+            '-' => {
+                tokens.push(ExprToken::Minus);
+                i += 1;
+            }
+            '*' => {
+                tokens.push(ExprToken::Star);
+                i += 1;
+            }
+            '/' => {
+                tokens.push(ExprToken::Slash);
+                i += 1;
+            }
+            '(' => {
+                tokens.push(ExprToken::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(ExprToken::RParen);
+                i += 1;
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let number_str: String = chars[start..i].iter().collect();
+                let number = number_str
+                    .parse::<f64>()
+                    .map_err(|_| format!("Invalid number '{}'", number_str))?;
+                tokens.push(ExprToken::Number(number));
+            }
+            other => return Err(format!("Unexpected character '{}'", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Shunting-yard: rearranges infix tokens into reverse Polish notation.
+fn to_rpn(tokens: &[ExprToken]) -> Result<Vec<ExprToken>, String> {
+    let mut output = Vec::new();
+    let mut operators: Vec<ExprToken> = Vec::new();
+
+    for token in tokens {
+        match token {
+            ExprToken::Number(_) => output.push(token.clone()),
+            ExprToken::Plus | ExprToken::Minus | ExprToken::Star | ExprToken::Slash => {
+                while let Some(top) = operators.last() {
+                    if *top == ExprToken::LParen || top.precedence() < token.precedence() {
+                        break;
+                    }
+                    output.push(operators.pop().unwrap());
+                }
+                operators.push(token.clone());
+            }
+            ExprToken::LParen => operators.push(token.clone()),
+            ExprToken::RParen => {
+                let mut closed = false;
+                while let Some(top) = operators.pop() {
+                    if top == ExprToken::LParen {
+                        closed = true;
+                        break;
+                    }
+                    output.push(top);
+                }
+                if !closed {
+                    return Err("Mismatched parentheses".to_string());
+                }
+            }
+        }
+    }
+
+    while let Some(top) = operators.pop() {
+        if top == ExprToken::LParen || top == ExprToken::RParen {
+            return Err("Mismatched parentheses".to_string());
+        }
+        output.push(top);
+    }
+
+    Ok(output)
+}
+
+fn eval_rpn(rpn: &[ExprToken]) -> Result<f64, String> {
+    let mut stack: Vec<f64> = Vec::new();
+
+    for token in rpn {
+        match token {
+            ExprToken::Number(n) => stack.push(*n),
+            op => {
+                let b = stack.pop().ok_or("Malformed expression")?;
+                let a = stack.pop().ok_or("Malformed expression")?;
+                let result = match op {
+                    ExprToken::Plus => a + b,
+                    ExprToken::Minus => a - b,
+                    ExprToken::Star => a * b,
+                    ExprToken::Slash => {
+                        if b == 0.0 {
+                            return Err("Division by zero".to_string());
+                        }
+                        a / b
+                    }
+                    _ => unreachable!(),
+                };
+                stack.push(result);
+            }
+        }
+    }
+
+    match stack.as_slice() {
+        [result] => Ok(*result),
+        _ => Err("Malformed expression".to_string()),
+    }
+}
+
+/// Evaluates an arithmetic expression over `+ - * / ( )` and f64 operands,
+/// e.g. `"12.5*3 + 4"`, via a shunting-yard pass to RPN followed by stack
+/// evaluation.
+fn evaluate_expression(expr: &str) -> Result<f64, String> {
+    let tokens = tokenize_expression(expr)?;
+    let rpn = to_rpn(&tokens)?;
+    eval_rpn(&rpn)
+}
+
+fn parse_currency_input(input: &str) -> Result<(f64, Currency, Vec<Currency>, Option<NaiveDate>), ParseError> {
     // Remove "cc" prefix and clean the input
     let cleaned = input.trim();
 
-    // Pattern 1: "100 usd in chf" or "100 usd chf"
-    let re1 = Regex::new(r"^(\d+(?:\.\d+)?)\s+([a-zA-Z]{3,4})(?:\s+in)?\s+([a-zA-Z]{3,4})$").unwrap();
+    // Pattern 1: "100 usd in chf", "100 usd chf", "100 usd in chf,eur,gbp" or
+    // "100 usd chf eur gbp", optionally followed by "on 2023-01-15" (or just
+    // the bare date) for a historical conversion. The amount is a full
+    // arithmetic expression (e.g. "12.5*3 + 4"), not just a literal number.
+    let re1 = Regex::new(
+        r"^([0-9.+\-*/()\s]+?)\s+([a-zA-Z]{3,4})(?:\s+in)?\s+([a-zA-Z]{3,4}(?:[,\s]+[a-zA-Z]{3,4})*)(?:\s+(?:on\s+)?(\d{4}-\d{2}-\d{2}))?$",
+    )
+    .unwrap();
 
     if let Some(caps) = re1.captures(cleaned) {
-        let amount: f64 = caps[1].parse().map_err(|_| "Invalid amount")?;
-        let from_currency = caps[2].to_uppercase();
-        let to_currency = caps[3].to_uppercase();
-        return Ok((amount, from_currency, to_currency));
+        let amount = evaluate_expression(&caps[1]).map_err(|_| ParseError::Format("Invalid amount".to_string()))?;
+        let from_currency = caps[2].parse::<Currency>().map_err(ParseError::Currency)?;
+
+        let to_currencies = caps[3]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|code| !code.is_empty())
+            .map(|code| code.parse::<Currency>().map_err(ParseError::Currency))
+            .collect::<Result<Vec<Currency>, ParseError>>()?;
+
+        let date = match caps.get(4) {
+            Some(m) => {
+                let parsed = NaiveDate::parse_from_str(m.as_str(), "%Y-%m-%d")
+                    .map_err(|_| ParseError::Date(format!("Invalid date '{}'", m.as_str())))?;
+                if parsed > Utc::now().date_naive() {
+                    return Err(ParseError::Date(format!(
+                        "Date '{}' is in the future",
+                        parsed
+                    )));
+                }
+                Some(parsed)
+            }
+            None => None,
+        };
+
+        return Ok((amount, from_currency, to_currencies, date));
     }
 
-    Err("Invalid format. Use: cc [amount] [from_currency] [to_currency] or cc [amount] [from_currency] in [to_currency]".to_string())
+    Err(ParseError::Format(
+        "Invalid format. Use: cc [amount] [from_currency] [to_currency[,to_currency...]] [on YYYY-MM-DD] or cc [amount] [from_currency] in [to_currency[,to_currency...]] [on YYYY-MM-DD]"
+            .to_string(),
+    ))
 }
 
-fn format_conversion_content(amount: f64, from: &str, to: &str, result: f64, rate: f64, date: &str) -> String {
+/// Renders one or more conversion results for the same source amount as a
+/// monospace table, one line per target currency.
+fn format_conversion_content(amount: f64, from: &Currency, results: &[(Currency, f64, f64)], date: &str) -> String {
+    let table: String = results
+        .iter()
+        .map(|(to, result, rate)| {
+            format!(
+                "<b>{} {}</b>   (1 {} = {:.6} {})",
+                format_amount(*result), to, from, rate, to
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
     format!(
         r#"<span font_desc="monospace">
 ─── <b><i>Currency Conversion</i></b> ───
 
-<b>{:.2} {}</b> = <b>{:.2} {}</b>
+<b>{} {}</b> converts to:
 
-Exchange Rate: 1 {} = {:.6} {}
-Inverse Rate: 1 {} = {:.6} {}
+{}
 
 Date: {}
 ────────────
 </span>"#,
-        amount,
-        from.to_uppercase(),
-        result,
-        to.to_uppercase(),
-        from.to_uppercase(),
-        rate,
-        to.to_uppercase(),
-        to.to_uppercase(),
-        1.0 / rate,
-        from.to_uppercase(),
-        date
+        format_amount(amount), from, table, date
     )
 }
 
-async fn perform_conversion(amount: f64, from: &str, to: &str) -> Result<(f64, f64, String), Box<dyn std::error::Error>> {
-    // If converting from the same currency, return 1:1
-    if from.eq_ignore_ascii_case(to) {
-        return Ok((amount, 1.0, "Today".to_string()));
+/// A source of exchange rates from one [`Currency`] into one or more others.
+#[async_trait::async_trait]
+trait RateProvider {
+    /// Fetches, for each of `targets`, the rate to convert 1 unit of `from`
+    /// into it, the inverse rate, and the date the rate is quoted for.
+    /// `date` requests historical rates instead of the latest ones; not
+    /// every provider can honor it. Results are returned in the same order
+    /// as `targets`.
+    async fn fetch_rates(
+        &self,
+        from: &Currency,
+        targets: &[Currency],
+        date: Option<NaiveDate>,
+    ) -> Result<Vec<(Currency, f64, f64, String)>, Box<dyn std::error::Error>>;
+}
+
+/// Fiat-to-fiat rates from the Frankfurter API. Does not support crypto assets.
+struct FrankfurterProvider;
+
+#[async_trait::async_trait]
+impl RateProvider for FrankfurterProvider {
+    async fn fetch_rates(
+        &self,
+        from: &Currency,
+        targets: &[Currency],
+        date: Option<NaiveDate>,
+    ) -> Result<Vec<(Currency, f64, f64, String)>, Box<dyn std::error::Error>> {
+        if from.is_crypto() || targets.iter().any(Currency::is_crypto) {
+            return Err("Frankfurter does not support cryptocurrencies".into());
+        }
+
+        let endpoint = match date {
+            Some(d) => d.format("%Y-%m-%d").to_string(),
+            None => "latest".to_string(),
+        };
+        // Request the full rate table (no `symbols` filter) so a successful
+        // fetch can be cached for offline use regardless of which targets
+        // happen to be asked for today.
+        let url = format!("https://api.frankfurter.dev/v1/{}?base={}", endpoint, from);
+
+        let mut response = surf::get(&url).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(format!("HTTP Error: {}", status).into());
+        }
+
+        let body_text = response.body_string().await?;
+        let frankfurter_response: FrankfurterResponse = serde_json::from_str(&body_text)?;
+
+        if date.is_none() {
+            save_rate_cache(from, &frankfurter_response);
+        }
+
+        targets
+            .iter()
+            .map(|target| {
+                frankfurter_response
+                    .rates
+                    .get(target.code())
+                    .map(|&rate| (*target, rate, 1.0 / rate, frankfurter_response.date.clone()))
+                    .ok_or_else(|| format!("Exchange rate data unavailable for '{}'", target).into())
+            })
+            .collect()
     }
+}
+
+#[derive(Debug, Deserialize)]
+struct AlphavantageResponse {
+    #[serde(rename = "Realtime Currency Exchange Rate")]
+    rate: AlphavantageRate,
+}
+
+#[derive(Debug, Deserialize)]
+struct AlphavantageRate {
+    #[serde(rename = "5. Exchange Rate")]
+    exchange_rate: String,
+    #[serde(rename = "6. Last Refreshed")]
+    last_refreshed: String,
+}
+
+/// Rates covering crypto assets (and fiat pairs) from an Alphavantage-style
+/// `CURRENCY_EXCHANGE_RATE` endpoint.
+struct AlphavantageProvider;
+
+impl AlphavantageProvider {
+    /// Alphavantage's `CURRENCY_EXCHANGE_RATE` endpoint only ever quotes a
+    /// single pair, so batches are resolved one request at a time.
+    async fn fetch_single(&self, from: &Currency, to: &Currency) -> Result<(f64, f64, String), Box<dyn std::error::Error>> {
+        let api_key = env::var("ALPHAVANTAGE_API_KEY").unwrap_or_else(|_| "demo".to_string());
+        let url = format!(
+            "https://www.alphavantage.co/query?function=CURRENCY_EXCHANGE_RATE&from_currency={}&to_currency={}&apikey={}",
+            from, to, api_key
+        );
+
+        let mut response = surf::get(&url).await?;
+        let status = response.status();
+
+        if !status.is_success() {
+            return Err(format!("HTTP Error: {}", status).into());
+        }
 
-    // Use Frankfurter API to get exchange rate
-    let url = format!(
-        "https://api.frankfurter.dev/v1/latest?base={}&symbols={}",
-        from.to_uppercase(),
-        to.to_uppercase()
-    );
+        let body_text = response.body_string().await?;
+        let alphavantage_response: AlphavantageResponse = serde_json::from_str(&body_text)
+            .map_err(|_| format!("No exchange rate data for '{}' to '{}'", from, to))?;
 
-    let mut response = surf::get(&url).await?;
-    let status = response.status();
+        let rate: f64 = alphavantage_response.rate.exchange_rate.parse()?;
+        let date = alphavantage_response
+            .rate
+            .last_refreshed
+            .split_whitespace()
+            .next()
+            .unwrap_or(&alphavantage_response.rate.last_refreshed)
+            .to_string();
 
-    if !status.is_success() {
-        return Err(format!("HTTP Error: {}", status).into());
+        Ok((rate, 1.0 / rate, date))
     }
+}
 
-    let body_text = response.body_string().await?;
-    let frankfurter_response: FrankfurterResponse = serde_json::from_str(&body_text)?;
+#[async_trait::async_trait]
+impl RateProvider for AlphavantageProvider {
+    async fn fetch_rates(
+        &self,
+        from: &Currency,
+        targets: &[Currency],
+        date: Option<NaiveDate>,
+    ) -> Result<Vec<(Currency, f64, f64, String)>, Box<dyn std::error::Error>> {
+        if date.is_some() {
+            return Err("This provider does not support historical rate lookups".into());
+        }
 
-    // Get the exchange rate for the target currency
-    if let Some(&rate) = frankfurter_response.rates.get(&to.to_uppercase()) {
-        let result = amount * rate;
-        Ok((result, rate, frankfurter_response.date))
-    } else {
-        Err(format!("Currency '{}' not supported or not found", to.to_uppercase()).into())
+        let mut results = Vec::with_capacity(targets.len());
+        for target in targets {
+            let (rate, inverse_rate, quoted_date) = self.fetch_single(from, target).await?;
+            results.push((*target, rate, inverse_rate, quoted_date));
+        }
+        Ok(results)
     }
 }
 
+/// Converts `amount` from `from` into every currency in `targets`, returning
+/// one `(target, result, rate)` tuple per target (same order as `targets`)
+/// plus the date the rates are quoted for.
+async fn perform_conversion(
+    amount: f64,
+    from: &Currency,
+    targets: &[Currency],
+    date: Option<NaiveDate>,
+) -> Result<(Vec<(Currency, f64, f64)>, String), Box<dyn std::error::Error>> {
+    let same_currency_label = date.map(|d| d.to_string()).unwrap_or_else(|| "Today".to_string());
+
+    // Same-currency targets convert 1:1 without a network round-trip; only
+    // the rest need to go out to a provider, in one batched call.
+    let to_fetch: Vec<Currency> = targets.iter().copied().filter(|t| t != from).collect();
+
+    let mut fetched: HashMap<Currency, (f64, f64, String)> = HashMap::new();
+    let mut quoted_date = same_currency_label.clone();
+
+    if !to_fetch.is_empty() {
+        let has_crypto = from.is_crypto() || to_fetch.iter().any(Currency::is_crypto);
+
+        // Crypto pairs go to Alphavantage first since Frankfurter can't serve
+        // them; fiat pairs prefer Frankfurter and fall back to Alphavantage
+        // on failure. Alphavantage has no historical endpoint, so a dated
+        // fiat query skips it entirely.
+        let providers: Vec<Box<dyn RateProvider>> = if has_crypto {
+            vec![Box::new(AlphavantageProvider), Box::new(FrankfurterProvider)]
+        } else if date.is_some() {
+            vec![Box::new(FrankfurterProvider)]
+        } else {
+            vec![Box::new(FrankfurterProvider), Box::new(AlphavantageProvider)]
+        };
+
+        let mut last_error: Option<Box<dyn std::error::Error>> = None;
+        let mut succeeded = false;
+        for provider in &providers {
+            match provider.fetch_rates(from, &to_fetch, date).await {
+                Ok(rates) => {
+                    for (target, rate, _inverse_rate, rate_date) in &rates {
+                        fetched.insert(*target, (*rate, amount * rate, rate_date.clone()));
+                    }
+                    if let Some((_, _, _, rate_date)) = rates.first() {
+                        quoted_date = rate_date.clone();
+                    }
+                    succeeded = true;
+                    break;
+                }
+                Err(e) => {
+                    // Keep the first failure, which is the most informative
+                    // one for the provider order we actually tried.
+                    if last_error.is_none() {
+                        last_error = Some(e);
+                    }
+                }
+            }
+        }
+
+        if !succeeded {
+            // Every provider failed (most likely offline); fall back to the
+            // last rate table we cached for this base currency rather than
+            // giving up outright.
+            if !from.is_crypto() && date.is_none() {
+                if let Some(cached) = load_rate_cache(from) {
+                    let all_cached = to_fetch
+                        .iter()
+                        .all(|target| cached.rates.contains_key(target.code()));
+                    if all_cached {
+                        for target in &to_fetch {
+                            let rate = cached.rates[target.code()];
+                            fetched.insert(*target, (rate, amount * rate, cached.date.clone()));
+                        }
+                        quoted_date = format!("{} (cached, offline)", cached.date);
+                        succeeded = true;
+                    }
+                }
+            }
+        }
+
+        if !succeeded {
+            return Err(last_error.unwrap_or_else(|| "No rate provider available".into()));
+        }
+    }
+
+    let mut results = Vec::with_capacity(targets.len());
+    for target in targets {
+        if target == from {
+            results.push((*target, amount, 1.0));
+        } else if let Some((rate, result, _)) = fetched.get(target) {
+            results.push((*target, *result, *rate));
+        } else {
+            return Err(format!("Exchange rate data unavailable for '{}'", target).into());
+        }
+    }
+
+    Ok((results, quoted_date))
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
@@ -139,10 +929,55 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Join all arguments except the program name
     let input = args[1..].join(" ");
 
-    let (amount, from_currency, to_currency) = match parse_currency_input(&input) {
+    let (amount, from_currency, to_currencies, date) = match parse_currency_input(&input) {
         Ok(parsed) => parsed,
-        Err(error_msg) => {
-            eprintln!("Parse Error: {}", error_msg);
+        Err(ParseError::Currency(err)) => {
+            eprintln!("Parse Error: {}", err);
+            let sherlock_error_response = SherlockPipeResponse {
+                title: "Currency Not Supported".to_string(),
+                content: format!(
+                    r#"<span font_desc="monospace">
+─── <b><i>Currency Not Supported</i></b> ───
+
+{}
+
+Supported currencies:
+{}
+────────────
+</span>"#,
+                    err,
+                    supported_currencies_block()
+                ),
+                next_content: String::new(),
+                actions: vec![],
+            };
+            println!("{}", serde_json::to_string(&sherlock_error_response).unwrap());
+            return Ok(());
+        }
+        Err(ParseError::Date(msg)) => {
+            eprintln!("Parse Error: {}", msg);
+            let sherlock_error_response = SherlockPipeResponse {
+                title: "Invalid Date".to_string(),
+                content: format!(
+                    r#"<span font_desc="monospace">
+─── <b><i>Invalid Date</i></b> ───
+
+{}
+
+Dates must be in YYYY-MM-DD format and cannot be in the future.
+Example: cc 100 usd chf on 2023-01-15
+────────────
+</span>"#,
+                    msg
+                ),
+                next_content: String::new(),
+                actions: vec![],
+            };
+            println!("{}", serde_json::to_string(&sherlock_error_response).unwrap());
+            return Ok(());
+        }
+        Err(ParseError::Format(msg)) => {
+            eprintln!("Parse Error: {}", msg);
             let sherlock_error_response = SherlockPipeResponse {
                 title: "Invalid Input Format".to_string(),
                 content: format!(
@@ -153,11 +988,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 • cc 50 eur in gbp
 • cc 1000 jpy usd
 • cc 25.5 cad aud
+• cc 100 usd in chf,eur,gbp
+• cc 12.5*3 + 4 eur usd
 
-Supported: 30+ major currencies including:
-USD, EUR, GBP, JPY, CHF, CAD, AUD, etc.
-
-Note: Cryptocurrencies not supported by this API
+Supported: 30+ major currencies plus BTC, ETH, and other crypto:
+USD, EUR, GBP, JPY, CHF, CAD, AUD, BTC, ETH, etc.
 ────────────
 </span>"#
                 ),
@@ -169,61 +1004,50 @@ Note: Cryptocurrencies not supported by this API
         }
     };
 
-    match perform_conversion(amount, &from_currency, &to_currency).await {
-        Ok((result, rate, date)) => {
-            let content = format_conversion_content(
-                amount,
-                &from_currency,
-                &to_currency,
-                result,
-                rate,
-                &date,
-            );
-
-            let action = ApplicationAction::from_conversion(
-                amount,
-                &from_currency,
-                &to_currency,
-                result,
-                rate,
-            );
+    match perform_conversion(amount, &from_currency, &to_currencies, date).await {
+        Ok((results, date)) => {
+            let content = format_conversion_content(amount, &from_currency, &results, &date);
+
+            let actions = results
+                .iter()
+                .map(|(to, result, rate)| {
+                    ApplicationAction::from_conversion(amount, &from_currency, to, *result, *rate)
+                })
+                .collect();
+
+            let title = if let [(to, result, _)] = results.as_slice() {
+                format!(
+                    "{} {} → {} {}",
+                    format_amount(amount),
+                    from_currency,
+                    format_amount(*result),
+                    to
+                )
+            } else {
+                format!(
+                    "{} {} → {}",
+                    format_amount(amount),
+                    from_currency,
+                    results
+                        .iter()
+                        .map(|(to, result, _)| format!("{} {}", format_amount(*result), to))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            };
 
             let sherlock_response = SherlockPipeResponse {
-                title: format!("{:.2} {} → {:.2} {}",
-                               amount,
-                               from_currency.to_uppercase(),
-                               result,
-                               to_currency.to_uppercase()
-                ),
+                title,
                 content: content.clone(),
                 next_content: content,
-                actions: vec![action],
+                actions,
             };
             println!("{}", serde_json::to_string(&sherlock_response).unwrap());
         }
         Err(e) => {
             eprintln!("Conversion failed: {}", e);
 
-            let error_content = if e.to_string().contains("Currency") && e.to_string().contains("not supported") {
-                format!(
-                    r#"<span font_desc="monospace">
-─── <b><i>Currency Not Supported</i></b> ───
-
-'{}' or '{}' is not supported by Frankfurter API.
-
-Supported currencies include:
-• Major: USD, EUR, GBP, JPY, CHF, CAD, AUD
-• European: SEK, NOK, DKK, PLN, CZK, HUF
-• Asian: CNY, HKD, SGD, KRW, INR, THB
-• Others: BRL, MXN, ZAR, TRY, RUB
-
-Note: Cryptocurrencies are not supported
-────────────
-</span>"#,
-                    from_currency.to_uppercase(),
-                    to_currency.to_uppercase()
-                )
-            } else if e.to_string().contains("HTTP Error") {
+            let error_content = if e.to_string().contains("HTTP Error") {
                 format!(
                     r#"<span font_desc="monospace">
 ─── <b><i>Network Error</i></b> ───
@@ -262,4 +1086,147 @@ Please verify currency codes and try again.
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn currency_from_str_accepts_supported_codes_case_insensitively() {
+        assert_eq!("usd".parse::<Currency>().unwrap(), Currency::Usd);
+        assert_eq!("USD".parse::<Currency>().unwrap(), Currency::Usd);
+        assert_eq!("btc".parse::<Currency>().unwrap(), Currency::Btc);
+        assert_eq!("doge".parse::<Currency>().unwrap(), Currency::Doge);
+    }
+
+    #[test]
+    fn currency_from_str_rejects_wrong_length() {
+        assert_eq!("us".parse::<Currency>(), Err(CurrencyError::InvalidLength(2)));
+        assert_eq!("dollar".parse::<Currency>(), Err(CurrencyError::InvalidLength(6)));
+    }
+
+    #[test]
+    fn currency_from_str_rejects_non_alphabetic_input() {
+        assert_eq!("u5d".parse::<Currency>(), Err(CurrencyError::InvalidCharacter('5')));
+    }
+
+    #[test]
+    fn currency_from_str_rejects_unknown_codes() {
+        assert_eq!(
+            "xyz".parse::<Currency>(),
+            Err(CurrencyError::Unsupported("XYZ".to_string()))
+        );
+    }
+
+    #[test]
+    fn evaluate_expression_handles_precedence_and_associativity() {
+        assert_eq!(evaluate_expression("12.5*3 + 4").unwrap(), 41.5);
+        assert_eq!(evaluate_expression("2 + 3 * 4").unwrap(), 14.0);
+        assert_eq!(evaluate_expression("20 - 5 - 5").unwrap(), 10.0);
+        assert_eq!(evaluate_expression("20 / 5 / 2").unwrap(), 2.0);
+    }
+
+    #[test]
+    fn evaluate_expression_handles_parentheses() {
+        assert_eq!(evaluate_expression("(2 + 3) * 4").unwrap(), 20.0);
+        assert_eq!(evaluate_expression("2 * (3 + (4 - 1))").unwrap(), 12.0);
+    }
+
+    #[test]
+    fn evaluate_expression_rejects_division_by_zero() {
+        assert!(evaluate_expression("1 / 0").is_err());
+    }
+
+    #[test]
+    fn evaluate_expression_rejects_malformed_input() {
+        assert!(evaluate_expression("1 + ").is_err());
+        assert!(evaluate_expression("(1 + 2").is_err());
+        assert!(evaluate_expression("1 + 2)").is_err());
+        assert!(evaluate_expression("1 $ 2").is_err());
+    }
+
+    #[test]
+    fn rate_cache_round_trips_through_disk() {
+        let temp_dir = env::temp_dir().join(format!("sherlock-currency-test-{}", std::process::id()));
+        env::set_var("XDG_CACHE_HOME", &temp_dir);
+
+        let response = FrankfurterResponse {
+            base: "USD".to_string(),
+            date: "2024-01-01".to_string(),
+            rates: HashMap::from([("EUR".to_string(), 0.9), ("GBP".to_string(), 0.8)]),
+        };
+
+        save_rate_cache(&Currency::Usd, &response);
+        let loaded = load_rate_cache(&Currency::Usd).expect("cached rates should round-trip");
+
+        assert_eq!(loaded.base, response.base);
+        assert_eq!(loaded.date, response.date);
+        assert_eq!(loaded.rates, response.rates);
+
+        let _ = fs::remove_dir_all(&temp_dir);
+        env::remove_var("XDG_CACHE_HOME");
+    }
+
+    #[test]
+    fn parse_currency_input_splits_comma_separated_targets() {
+        let (amount, from, targets, date) = parse_currency_input("100 usd in chf,eur,gbp").unwrap();
+        assert_eq!(amount, 100.0);
+        assert_eq!(from, Currency::Usd);
+        assert_eq!(targets, vec![Currency::Chf, Currency::Eur, Currency::Gbp]);
+        assert_eq!(date, None);
+    }
+
+    #[test]
+    fn parse_currency_input_splits_whitespace_separated_targets_with_trailing_date() {
+        let (amount, from, targets, date) =
+            parse_currency_input("100 usd chf eur gbp on 2023-01-15").unwrap();
+        assert_eq!(amount, 100.0);
+        assert_eq!(from, Currency::Usd);
+        assert_eq!(targets, vec![Currency::Chf, Currency::Eur, Currency::Gbp]);
+        assert_eq!(date, Some(NaiveDate::from_ymd_opt(2023, 1, 15).unwrap()));
+    }
+
+    #[test]
+    fn parse_currency_input_evaluates_arithmetic_amount_expressions() {
+        let (amount, from, targets, date) = parse_currency_input("12.5*3 + 4 eur usd").unwrap();
+        assert_eq!(amount, 41.5);
+        assert_eq!(from, Currency::Eur);
+        assert_eq!(targets, vec![Currency::Usd]);
+        assert_eq!(date, None);
+    }
+
+    #[test]
+    fn parse_currency_input_rejects_future_dates() {
+        let far_future = Utc::now().date_naive() + chrono::Duration::days(365);
+        let input = format!("100 usd chf on {}", far_future.format("%Y-%m-%d"));
+        match parse_currency_input(&input) {
+            Err(ParseError::Date(msg)) => assert!(msg.contains("future")),
+            other => panic!("expected ParseError::Date, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_currency_input_rejects_malformed_dates() {
+        assert!(matches!(
+            parse_currency_input("100 usd chf on 2023-13-40"),
+            Err(ParseError::Date(_))
+        ));
+    }
+
+    #[test]
+    fn parse_currency_input_rejects_unsupported_currency() {
+        assert!(matches!(
+            parse_currency_input("100 usd xyz"),
+            Err(ParseError::Currency(_))
+        ));
+    }
+
+    #[test]
+    fn parse_currency_input_rejects_unparseable_garbage() {
+        assert!(matches!(
+            parse_currency_input("not a conversion"),
+            Err(ParseError::Format(_))
+        ));
+    }
+}